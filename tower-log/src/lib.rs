@@ -3,16 +3,66 @@
 //! This is useful if those errors would otherwise be ignored or
 //! transformed into another error type that might provide less
 //! information, such as by `tower-buffer`.
+//!
+//! With the `tracing` feature enabled, records are emitted as structured
+//! `tracing` spans/events instead of flat `log` messages. See
+//! [`log_line`](struct.LogErrors.html) for details.
 
 extern crate futures;
 extern crate tower;
 extern crate log;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 
 use futures::{Async, Future, Poll};
 use tower::{Service, NewService};
+use tower::Layer;
 
 use std::error::Error;
 use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+mod retry;
+mod sampling;
+
+pub use retry::{ExponentialBackoff, ResponseFuture, RetryLogged};
+pub use sampling::Sampler;
+
+use sampling::SampleOutcome;
+
+/// Emit a `tracing` event at a `log::Level` known only at runtime.
+///
+/// `tracing`'s own `event!` macro requires the level to be one of the
+/// `tracing::Level` associated constants written out literally, since level
+/// filtering is resolved at the callsite at compile time. Since this crate's
+/// level is configured at runtime (to mirror the `log`-based API), dispatch
+/// to the matching literal-level macro by hand.
+#[cfg(feature = "tracing")]
+macro_rules! tracing_event {
+    ($level:expr, $($rest:tt)+) => {
+        match $level {
+            log::Level::Error => tracing::error!($($rest)+),
+            log::Level::Warn => tracing::warn!($($rest)+),
+            log::Level::Info => tracing::info!($($rest)+),
+            log::Level::Debug => tracing::debug!($($rest)+),
+            log::Level::Trace => tracing::trace!($($rest)+),
+        }
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! tracing_span {
+    ($level:expr, $($rest:tt)+) => {
+        match $level {
+            log::Level::Error => tracing::error_span!($($rest)+),
+            log::Level::Warn => tracing::warn_span!($($rest)+),
+            log::Level::Info => tracing::info_span!($($rest)+),
+            log::Level::Debug => tracing::debug_span!($($rest)+),
+            log::Level::Trace => tracing::trace_span!($($rest)+),
+        }
+    };
+}
 
 /// Wrap a `Service` or `NewService` with `LogErrors` middleware.
 ///
@@ -97,22 +147,38 @@ macro_rules! log_responses {
 pub struct LogErrors<T> {
     inner: T,
     level: log::Level,
+    source_chain: bool,
+    sampler: Arc<Mutex<Sampler>>,
     target: Option<&'static str>,
     module_path: Option<&'static str>,
     file: Option<&'static str>,
     line: Option<u32>,
+    #[cfg(feature = "tracing")]
+    span: Option<tracing::Span>,
 }
 
+/// The maximum number of `Error::source()` links that will be followed when
+/// [`with_source_chain`] is enabled, to guard against cyclical error chains.
+///
+/// [`with_source_chain`]: struct.LogErrors.html#method.with_source_chain
+const MAX_SOURCE_CHAIN_DEPTH: usize = 32;
+
 /// Logs successful responses.
 #[derive(Clone, Debug)]
 pub struct LogResponses<T> {
     inner: T,
     level: log::Level,
     not_ready: bool,
+    slow_after: Option<Duration>,
+    slow_level: log::Level,
+    start: Option<Instant>,
+    sampler: Arc<Mutex<Sampler>>,
     target: Option<&'static str>,
     module_path: Option<&'static str>,
     file: Option<&'static str>,
     line: Option<u32>,
+    #[cfg(feature = "tracing")]
+    span: Option<tracing::Span>,
 }
 
 
@@ -141,10 +207,14 @@ impl<T> LogErrors<T> {
         LogErrors {
             inner,
             level: log::Level::Error,
+            source_chain: false,
+            sampler: Arc::new(Mutex::new(Sampler::always())),
             target: None,
             module_path: None,
             file: None,
             line: None,
+            #[cfg(feature = "tracing")]
+            span: None,
         }
     }
 
@@ -178,18 +248,80 @@ impl<T> LogErrors<T> {
         self
     }
 
+    /// Set whether or not this middleware should walk the error's
+    /// `Error::source()` chain and log each link down to the root cause.
+    ///
+    /// This is disabled by default, which preserves the existing behavior of
+    /// logging only the top-level error. Enabling it is especially useful
+    /// when an underlying error has been collapsed into a lossy wrapper, such
+    /// as by `tower-buffer`, since capturing the chain at the edge preserves
+    /// diagnostic detail that would otherwise be discarded.
+    pub fn with_source_chain(mut self, source_chain: bool) -> Self {
+        self.source_chain = source_chain;
+        self
+    }
+
+    /// Set how records produced by this middleware are sampled.
+    ///
+    /// By default every record is logged (`Sampler::always()`). Use
+    /// `Sampler::sample_rate` to log records probabilistically, or
+    /// `Sampler::max_per_interval` to token-bucket rate limit them, so that a
+    /// downstream service failing on every poll does not flood the log
+    /// pipeline.
+    pub fn sampled(mut self, sampler: Sampler) -> Self {
+        self.sampler = Arc::new(Mutex::new(sampler));
+        self
+    }
+
     fn child<U>(&self, inner: U) -> LogErrors<U> {
         LogErrors {
             inner,
             level: self.level,
+            source_chain: self.source_chain,
+            sampler: self.sampler.clone(),
             target: self.target,
             module_path: self.module_path,
             file: self.file,
             line: self.line,
+            #[cfg(feature = "tracing")]
+            span: None,
+        }
+    }
+
+    fn error_message<E: Error>(&self, error: &E) -> String {
+        if self.source_chain {
+            let mut message = format!("{}", error);
+            let mut source = error.source();
+            let mut depth = 0;
+            while let Some(cause) = source {
+                if depth >= MAX_SOURCE_CHAIN_DEPTH {
+                    break;
+                }
+                message.push_str(&format!("; caused by: {}", cause));
+                source = cause.source();
+                depth += 1;
+            }
+            message
+        } else {
+            format!("{}", error)
         }
     }
 
+    #[cfg(not(feature = "tracing"))]
     fn log_line<E: Error>(&self, error: &E, context: &'static str) {
+        // A poisoned lock (e.g. a panic mid-sample) shouldn't permanently
+        // silence every other connection sharing this sampler, so recover
+        // the inner state rather than propagating the panic.
+        let suppressed = match self.sampler.lock().unwrap_or_else(|e| e.into_inner()).sample() {
+            SampleOutcome::Drop => return,
+            SampleOutcome::Log => None,
+            SampleOutcome::LogWithSuppressed(n) => Some(n),
+        };
+        let message = self.error_message(error);
+        let message = match suppressed {
+            Some(n) => format!("suppressed {} records; {}: {}", n, context, message),
+            None => format!("{}: {}", context, message),
+        };
         log::Log::log(
             log::logger(),
             &log::RecordBuilder::new()
@@ -205,12 +337,47 @@ impl<T> LogErrors<T> {
                     self.module_path
                         .or(self.target)
                         .or_else(|| Some(module_path!())))
-                .args(format_args!("{}: {}", context, error))
+                .args(format_args!("{}", message))
                 .build()
         )
 
     }
 
+    /// Emit the current error as a structured `tracing` event on the span
+    /// opened for this call in [`call`](#method.call), recording the error
+    /// and the poll context (`Service::poll_ready` or `Future::poll`) as
+    /// fields rather than flattening them into one message.
+    #[cfg(feature = "tracing")]
+    fn log_line<E: Error>(&self, error: &E, context: &'static str) {
+        // A poisoned lock (e.g. a panic mid-sample) shouldn't permanently
+        // silence every other connection sharing this sampler, so recover
+        // the inner state rather than propagating the panic.
+        let suppressed = match self.sampler.lock().unwrap_or_else(|e| e.into_inner()).sample() {
+            SampleOutcome::Drop => return,
+            SampleOutcome::Log => None,
+            SampleOutcome::LogWithSuppressed(n) => Some(n),
+        };
+        let message = self.error_message(error);
+        let target = self.target.or(self.module_path).unwrap_or_else(|| module_path!());
+        match &self.span {
+            Some(span) => {
+                let _enter = span.enter();
+                tracing_event!(self.level, target: target, context, error = %message, suppressed = suppressed.unwrap_or(0));
+            }
+            None => {
+                tracing_event!(self.level, target: target, context, error = %message, suppressed = suppressed.unwrap_or(0));
+            }
+        }
+    }
+
+    /// Open the `tracing` span for a call, recorded as structured context on
+    /// the events emitted by [`log_line`](#method.log_line) for that call.
+    #[cfg(feature = "tracing")]
+    fn make_span(&self) -> tracing::Span {
+        let target = self.target.or(self.module_path).unwrap_or_else(|| module_path!());
+        tracing_span!(self.level, target: target, "tower_log_errors::call")
+    }
+
 }
 
 impl<T> Future for LogErrors<T>
@@ -248,7 +415,12 @@ where
 
     fn call(&mut self, req: Self::Request) -> Self::Future {
         let inner = self.inner.call(req);
-        self.child(inner)
+        let mut future = self.child(inner);
+        #[cfg(feature = "tracing")]
+        {
+            future.span = Some(self.make_span());
+        }
+        future
     }
 }
 
@@ -271,6 +443,116 @@ where
     }
 }
 
+/// A `Layer` that wraps services with `LogErrors` middleware.
+///
+/// This allows `LogErrors` to be composed into a `ServiceBuilder` stack
+/// alongside other layers, rather than having to nest `LogErrors::new`
+/// calls by hand.
+#[derive(Clone, Debug)]
+pub struct LogErrorsLayer {
+    level: log::Level,
+    source_chain: bool,
+    sampler: Sampler,
+    target: Option<&'static str>,
+    module_path: Option<&'static str>,
+    file: Option<&'static str>,
+    line: Option<u32>,
+}
+
+// ===== impl LogErrorsLayer =====
+
+impl LogErrorsLayer {
+
+    /// Construct a new `LogErrorsLayer`.
+    ///
+    /// The log level will default to `Level::Error` but may be changed with
+    /// the [`at_level`] function.
+    ///
+    /// [`at_level`]: struct.LogErrorsLayer.html#method.at_level
+    pub fn new() -> Self {
+        LogErrorsLayer {
+            level: log::Level::Error,
+            source_chain: false,
+            sampler: Sampler::always(),
+            target: None,
+            module_path: None,
+            file: None,
+            line: None,
+        }
+    }
+
+    /// Set the log level of the produced log records.
+    ///
+    /// Log records will be logged at the `Error` level by default.
+    pub fn at_level(mut self, level: log::Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set the target of the produced log records.
+    ///
+    /// The target will default to the module path of the `LogErrors`
+    /// middleware by default.
+    pub fn with_target(mut self, target: &'static str) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Set the module path of the produced log records to the given string.
+    pub fn in_module(mut self, module_path: &'static str) -> Self {
+        self.module_path = Some(module_path);
+        self
+    }
+
+    /// Set the file and line number of the produced log records.
+    pub fn at_location(mut self, file: &'static str, line: u32) -> Self {
+        self.file = Some(file);
+        self.line = Some(line);
+        self
+    }
+
+    /// Set whether or not this middleware should walk the error's
+    /// `Error::source()` chain and log each link down to the root cause.
+    pub fn with_source_chain(mut self, source_chain: bool) -> Self {
+        self.source_chain = source_chain;
+        self
+    }
+
+    /// Set how records produced by this middleware are sampled.
+    ///
+    /// By default every record is logged (`Sampler::always()`).
+    pub fn sampled(mut self, sampler: Sampler) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+}
+
+impl Default for LogErrorsLayer {
+    fn default() -> Self {
+        LogErrorsLayer::new()
+    }
+}
+
+impl<S> Layer<S> for LogErrorsLayer {
+    type Service = LogErrors<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LogErrors {
+            inner,
+            level: self.level,
+            source_chain: self.source_chain,
+            sampler: Arc::new(Mutex::new(self.sampler.clone())),
+            target: self.target,
+            module_path: self.module_path,
+            file: self.file,
+            line: self.line,
+            #[cfg(feature = "tracing")]
+            span: None,
+        }
+    }
+}
+
 // ===== impl LogResponses =====
 
 impl<T> LogResponses<T> {
@@ -300,10 +582,16 @@ impl<T> LogResponses<T> {
             inner,
             level: log::Level::Debug,
             not_ready: false,
+            slow_after: None,
+            slow_level: log::Level::Warn,
+            start: None,
+            sampler: Arc::new(Mutex::new(Sampler::always())),
             target: None,
             module_path: None,
             file: None,
             line: None,
+            #[cfg(feature = "tracing")]
+            span: None,
         }
     }
 
@@ -344,23 +632,95 @@ impl<T> LogResponses<T> {
         self
     }
 
+    /// Escalate the log level to [`at_slow_level`] when a call takes longer
+    /// than `threshold` to resolve.
+    ///
+    /// The elapsed time is measured from when `Service::call` is invoked to
+    /// when the returned `Future` resolves, and is included in the log
+    /// record regardless of whether the threshold was exceeded.
+    ///
+    /// [`at_slow_level`]: struct.LogResponses.html#method.at_slow_level
+    pub fn slow_after(mut self, threshold: Duration) -> Self {
+        self.slow_after = Some(threshold);
+        self
+    }
+
+    /// Set the log level used for calls that exceed the [`slow_after`]
+    /// threshold.
+    ///
+    /// Defaults to `Level::Warn`.
+    ///
+    /// [`slow_after`]: struct.LogResponses.html#method.slow_after
+    pub fn at_slow_level(mut self, level: log::Level) -> Self {
+        self.slow_level = level;
+        self
+    }
+
+    /// Set how records produced by this middleware are sampled.
+    ///
+    /// By default every record is logged (`Sampler::always()`). Use
+    /// `Sampler::sample_rate` to log records probabilistically, or
+    /// `Sampler::max_per_interval` to token-bucket rate limit them, so that a
+    /// downstream service succeeding (or failing) on every poll does not
+    /// flood the log pipeline.
+    pub fn sampled(mut self, sampler: Sampler) -> Self {
+        self.sampler = Arc::new(Mutex::new(sampler));
+        self
+    }
+
     fn child<U>(&self, inner: U) -> LogResponses<U> {
         LogResponses {
             inner,
             not_ready: self.not_ready,
             level: self.level,
+            slow_after: self.slow_after,
+            slow_level: self.slow_level,
+            start: None,
+            sampler: self.sampler.clone(),
             target: self.target,
             module_path: self.module_path,
             file: self.file,
             line: self.line,
+            #[cfg(feature = "tracing")]
+            span: None,
         }
     }
 
-    fn log_line<R: fmt::Debug>(&self, resp: &R, context: &'static str) {
+    fn effective_level(&self, elapsed: Option<Duration>) -> log::Level {
+        match elapsed {
+            Some(elapsed) if self.slow_after.map_or(false, |t| elapsed > t) =>
+                self.slow_level,
+            _ => self.level,
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    fn log_line<R: fmt::Debug>(&self,
+                                resp: &R,
+                                context: &'static str,
+                                elapsed: Option<Duration>)
+    {
+        // A poisoned lock (e.g. a panic mid-sample) shouldn't permanently
+        // silence every other connection sharing this sampler, so recover
+        // the inner state rather than propagating the panic.
+        let suppressed = match self.sampler.lock().unwrap_or_else(|e| e.into_inner()).sample() {
+            SampleOutcome::Drop => return,
+            SampleOutcome::Log => None,
+            SampleOutcome::LogWithSuppressed(n) => Some(n),
+        };
+        let level = self.effective_level(elapsed);
+        let message = match elapsed {
+            Some(elapsed) => format!("{}: {:?} (took {:?})", context, resp, elapsed),
+            None => format!("{}: {:?}", context, resp),
+        };
+        let message = match suppressed {
+            Some(n) => format!("suppressed {} records; {}", n, message),
+            None => message,
+        };
         log::Log::log(
             log::logger(),
             &log::RecordBuilder::new()
-                .level(self.level)
+                .level(level)
                 .file(self.file.or_else(|| Some(file!())))
                 .line(self.line.or_else(|| Some(line!())))
                 .target(
@@ -372,23 +732,72 @@ impl<T> LogResponses<T> {
                     self.module_path
                         .or(self.target)
                         .or_else(|| Some(module_path!())))
-                .args(format_args!("{}: {:?}", context, resp))
+                .args(format_args!("{}", message))
                 .build()
         )
     }
 
+    /// Emit the current response as a structured `tracing` event on the span
+    /// opened for this call in [`call`](#method.call), recording the
+    /// response, poll context, and elapsed time as fields rather than
+    /// flattening them into one message.
+    #[cfg(feature = "tracing")]
+    fn log_line<R: fmt::Debug>(&self,
+                                resp: &R,
+                                context: &'static str,
+                                elapsed: Option<Duration>)
+    {
+        // A poisoned lock (e.g. a panic mid-sample) shouldn't permanently
+        // silence every other connection sharing this sampler, so recover
+        // the inner state rather than propagating the panic.
+        let suppressed = match self.sampler.lock().unwrap_or_else(|e| e.into_inner()).sample() {
+            SampleOutcome::Drop => return,
+            SampleOutcome::Log => 0,
+            SampleOutcome::LogWithSuppressed(n) => n,
+        };
+        let level = self.effective_level(elapsed);
+        let target = self.target.or(self.module_path).unwrap_or_else(|| module_path!());
+        let resp = format!("{:?}", resp);
+        match (&self.span, elapsed) {
+            (Some(span), Some(elapsed)) => {
+                let _enter = span.enter();
+                tracing_event!(level, target: target, context, response = %resp, elapsed = ?elapsed, suppressed);
+            }
+            (Some(span), None) => {
+                let _enter = span.enter();
+                tracing_event!(level, target: target, context, response = %resp, suppressed);
+            }
+            (None, Some(elapsed)) => {
+                tracing_event!(level, target: target, context, response = %resp, elapsed = ?elapsed, suppressed);
+            }
+            (None, None) => {
+                tracing_event!(level, target: target, context, response = %resp, suppressed);
+            }
+        }
+    }
+
+    /// Open the `tracing` span for a call, recorded as structured context on
+    /// the events emitted by [`log_line`](#method.log_line) for that call.
+    #[cfg(feature = "tracing")]
+    fn make_span(&self) -> tracing::Span {
+        let target = self.target.or(self.module_path).unwrap_or_else(|| module_path!());
+        tracing_span!(self.level, target: target, "tower_log::call")
+    }
+
     fn log_poll<R: fmt::Debug>(&self,
                                 poll: Async<R>,
-                                context: &'static str)
+                                context: &'static str,
+                                start: Option<Instant>)
                                 -> Async<R>
     {
         match poll {
             ref not_ready @ Async::NotReady if self.not_ready => {
-                self.log_line(not_ready, context);
+                self.log_line(not_ready, context, None);
                 Async::NotReady
             },
             Async::Ready(rsp) => {
-                self.log_line(&rsp, context);
+                let elapsed = start.map(|start| start.elapsed());
+                self.log_line(&rsp, context, elapsed);
                 Async::Ready(rsp)
             },
             rsp => rsp,
@@ -406,8 +815,9 @@ where
     type Error = T::Error;
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let start = self.start;
         let poll = self.inner.poll();
-        poll.map(|poll| self.log_poll(poll, "Future::poll"))
+        poll.map(|poll| self.log_poll(poll, "Future::poll", start))
     }
 }
 
@@ -423,12 +833,18 @@ where
 
     fn poll_ready(&mut self) -> Poll<(), Self::Error> {
         let poll = self.inner.poll_ready();
-        poll.map(|poll| self.log_poll(poll, "Service::poll_ready"))
+        poll.map(|poll| self.log_poll(poll, "Service::poll_ready", None))
     }
 
     fn call(&mut self, req: Self::Request) -> Self::Future {
         let inner = self.inner.call(req);
-        self.child(inner)
+        let mut future = self.child(inner);
+        future.start = Some(Instant::now());
+        #[cfg(feature = "tracing")]
+        {
+            future.span = Some(self.make_span());
+        }
+        future
     }
 }
 
@@ -449,4 +865,144 @@ where
     fn new_service(&self) -> Self::Future {
         self.child(self.inner.new_service())
     }
+}
+
+/// A `Layer` that wraps services with `LogResponses` middleware.
+///
+/// This allows `LogResponses` to be composed into a `ServiceBuilder` stack
+/// alongside other layers, rather than having to nest `LogResponses::new`
+/// calls by hand.
+#[derive(Clone, Debug)]
+pub struct LogResponsesLayer {
+    level: log::Level,
+    not_ready: bool,
+    slow_after: Option<Duration>,
+    slow_level: log::Level,
+    sampler: Sampler,
+    target: Option<&'static str>,
+    module_path: Option<&'static str>,
+    file: Option<&'static str>,
+    line: Option<u32>,
+}
+
+// ===== impl LogResponsesLayer =====
+
+impl LogResponsesLayer {
+
+    /// Construct a new `LogResponsesLayer`.
+    ///
+    /// The log level will default to `Level::Debug` but may be changed with
+    /// the [`at_level`] function. `Async::NotReady` responses will not be
+    /// logged by default, but may be enabled with the [`log_not_ready`]
+    /// method.
+    ///
+    /// [`at_level`]: struct.LogResponsesLayer.html#method.at_level
+    /// [`log_not_ready`]: struct.LogResponsesLayer.html#method.log_not_ready
+    pub fn new() -> Self {
+        LogResponsesLayer {
+            level: log::Level::Debug,
+            not_ready: false,
+            slow_after: None,
+            slow_level: log::Level::Warn,
+            sampler: Sampler::always(),
+            target: None,
+            module_path: None,
+            file: None,
+            line: None,
+        }
+    }
+
+    /// Set the log level of the produced log records.
+    ///
+    /// Log records will be logged at the `Debug` level by default.
+    pub fn at_level(mut self, level: log::Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set the target of the produced log records.
+    ///
+    /// The target will default to the module path of the `LogResponses`
+    /// middleware by default.
+    pub fn with_target(mut self, target: &'static str) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Set the module path of the produced log records to the given string.
+    pub fn in_module(mut self, module_path: &'static str) -> Self {
+        self.module_path = Some(module_path);
+        self
+    }
+
+    /// Set the file and line number of the produced log records.
+    pub fn at_location(mut self, file: &'static str, line: u32) -> Self {
+        self.file = Some(file);
+        self.line = Some(line);
+        self
+    }
+
+    /// Set whether or not this middleware should log `Async::NotReady`
+    /// responses.
+    pub fn log_not_ready(mut self, not_ready: bool) -> Self {
+        self.not_ready = not_ready;
+        self
+    }
+
+    /// Escalate the log level to [`at_slow_level`] when a call takes longer
+    /// than `threshold` to resolve.
+    ///
+    /// [`at_slow_level`]: struct.LogResponsesLayer.html#method.at_slow_level
+    pub fn slow_after(mut self, threshold: Duration) -> Self {
+        self.slow_after = Some(threshold);
+        self
+    }
+
+    /// Set the log level used for calls that exceed the [`slow_after`]
+    /// threshold.
+    ///
+    /// Defaults to `Level::Warn`.
+    ///
+    /// [`slow_after`]: struct.LogResponsesLayer.html#method.slow_after
+    pub fn at_slow_level(mut self, level: log::Level) -> Self {
+        self.slow_level = level;
+        self
+    }
+
+    /// Set how records produced by this middleware are sampled.
+    ///
+    /// By default every record is logged (`Sampler::always()`).
+    pub fn sampled(mut self, sampler: Sampler) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+}
+
+impl Default for LogResponsesLayer {
+    fn default() -> Self {
+        LogResponsesLayer::new()
+    }
+}
+
+impl<S> Layer<S> for LogResponsesLayer {
+    type Service = LogResponses<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LogResponses {
+            inner,
+            level: self.level,
+            not_ready: self.not_ready,
+            slow_after: self.slow_after,
+            slow_level: self.slow_level,
+            start: None,
+            sampler: Arc::new(Mutex::new(self.sampler.clone())),
+            target: self.target,
+            module_path: self.module_path,
+            file: self.file,
+            line: self.line,
+            #[cfg(feature = "tracing")]
+            span: None,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,370 @@
+//! Retries calls to a `Service` that fail with a retryable error, using
+//! exponential backoff between attempts.
+//!
+//! This sits alongside [`LogErrors`] and [`LogResponses`]: since this crate
+//! already observes errors as they surface, it is a natural place to retry
+//! transient failures instead of propagating them immediately.
+//!
+//! [`LogErrors`]: ../struct.LogErrors.html
+//! [`LogResponses`]: ../struct.LogResponses.html
+
+extern crate rand;
+extern crate tokio_timer;
+
+use futures::{Async, Future, Poll};
+use tower::Service;
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use self::tokio_timer::Delay;
+
+/// An exponential backoff policy for [`RetryLogged`].
+///
+/// The delay before the `n`th retry is `min_delay * factor.powi(n - 1)`,
+/// never less than `min_delay` and capped at `max_delay`. With [`full_jitter`] enabled, the delay actually
+/// waited is chosen uniformly at random from `[0, computed_delay]`.
+///
+/// [`RetryLogged`]: struct.RetryLogged.html
+/// [`full_jitter`]: struct.ExponentialBackoff.html#method.full_jitter
+#[derive(Clone, Copy, Debug)]
+pub struct ExponentialBackoff {
+    min_delay: Duration,
+    max_delay: Duration,
+    factor: f64,
+    max_retries: usize,
+    jitter: bool,
+}
+
+impl ExponentialBackoff {
+
+    /// Construct a new `ExponentialBackoff` policy.
+    ///
+    /// `min_delay` is the delay before the first retry, `max_delay` caps the
+    /// delay of any later retry, `factor` is the multiplier applied to the
+    /// delay after each attempt, and `max_retries` is the total number of
+    /// retries allowed before the error is given up on and propagated.
+    pub fn new(min_delay: Duration,
+               max_delay: Duration,
+               factor: f64,
+               max_retries: usize)
+               -> Self
+    {
+        ExponentialBackoff {
+            min_delay,
+            max_delay,
+            factor,
+            max_retries,
+            jitter: false,
+        }
+    }
+
+    /// Set whether the computed delay should be "full jitter"ed: rather than
+    /// waiting the computed delay exactly, wait a delay chosen uniformly at
+    /// random from `[0, computed_delay]`.
+    ///
+    /// Disabled by default.
+    pub fn full_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The maximum number of retries this policy allows.
+    pub fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
+    /// Compute the delay to wait before the given retry attempt (counting
+    /// from 1), or `None` if `attempt` exceeds [`max_retries`].
+    ///
+    /// [`max_retries`]: struct.ExponentialBackoff.html#method.max_retries
+    pub fn delay_for(&self, attempt: usize) -> Option<Duration> {
+        if attempt == 0 || attempt > self.max_retries {
+            return None;
+        }
+
+        let scale = self.factor.powi(attempt as i32 - 1).max(1.0);
+        let millis = (duration_as_millis(self.min_delay) * scale)
+            .min(duration_as_millis(self.max_delay));
+
+        let millis = if self.jitter {
+            rand::random::<f64>() * millis
+        } else {
+            millis
+        };
+
+        Some(Duration::from_millis(millis as u64))
+    }
+
+}
+
+fn duration_as_millis(d: Duration) -> f64 {
+    d.as_secs() as f64 * 1000.0 + f64::from(d.subsec_nanos()) / 1_000_000.0
+}
+
+// ===== impl RetryLogged =====
+
+/// Wraps a `Service` so that retryable errors are retried with exponential
+/// backoff instead of being propagated immediately.
+///
+/// Each retry is logged, noting the attempt number and the backoff delay,
+/// using the same `target`/`module_path`/`file`/`line` configuration as
+/// [`LogErrors`] and [`LogResponses`].
+///
+/// [`LogErrors`]: ../struct.LogErrors.html
+/// [`LogResponses`]: ../struct.LogResponses.html
+#[derive(Clone, Debug)]
+pub struct RetryLogged<S, P> {
+    inner: S,
+    backoff: ExponentialBackoff,
+    should_retry: P,
+    level: log::Level,
+    target: Option<&'static str>,
+    module_path: Option<&'static str>,
+    file: Option<&'static str>,
+    line: Option<u32>,
+}
+
+impl<S, P> RetryLogged<S, P> {
+
+    /// Construct a new `RetryLogged` middleware that wraps the given
+    /// `Service`, retrying errors accepted by `should_retry` according to
+    /// `backoff`.
+    ///
+    /// The log level will default to `Level::Warn` but may be changed with
+    /// the [`at_level`] function.
+    ///
+    /// [`at_level`]: struct.RetryLogged.html#method.at_level
+    pub fn new(inner: S, backoff: ExponentialBackoff, should_retry: P) -> Self {
+        RetryLogged {
+            inner,
+            backoff,
+            should_retry,
+            level: log::Level::Warn,
+            target: None,
+            module_path: None,
+            file: None,
+            line: None,
+        }
+    }
+
+    /// Set the log level of the produced retry log records.
+    ///
+    /// Log records will be logged at the `Warn` level by default.
+    pub fn at_level(mut self, level: log::Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set the target of the produced log records.
+    ///
+    /// The target will default to the module path of the `RetryLogged`
+    /// middleware by default.
+    pub fn with_target(mut self, target: &'static str) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Set the module path of the produced log records to the given string.
+    pub fn in_module(mut self, module_path: &'static str) -> Self {
+        self.module_path = Some(module_path);
+        self
+    }
+
+    /// Set the file and line number of the produced log records.
+    pub fn at_location(mut self, file: &'static str, line: u32) -> Self {
+        self.file = Some(file);
+        self.line = Some(line);
+        self
+    }
+
+}
+
+impl<S, P> Service for RetryLogged<S, P>
+where
+    S: Service + Clone,
+    S::Request: Clone,
+    S::Error: fmt::Display,
+    P: Fn(&S::Error) -> bool + Clone,
+{
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S, P>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let future = self.inner.call(request.clone());
+        ResponseFuture {
+            service: self.inner.clone(),
+            request,
+            backoff: self.backoff,
+            should_retry: self.should_retry.clone(),
+            attempt: 0,
+            state: State::Called(future),
+            level: self.level,
+            target: self.target,
+            module_path: self.module_path,
+            file: self.file,
+            line: self.line,
+        }
+    }
+}
+
+enum State<F> {
+    Called(F),
+    Waiting(Delay),
+    PollReady,
+}
+
+/// The `Future` returned by [`RetryLogged`]'s `Service` implementation.
+///
+/// [`RetryLogged`]: struct.RetryLogged.html
+pub struct ResponseFuture<S, P>
+where
+    S: Service,
+{
+    service: S,
+    request: S::Request,
+    backoff: ExponentialBackoff,
+    should_retry: P,
+    attempt: usize,
+    state: State<S::Future>,
+    level: log::Level,
+    target: Option<&'static str>,
+    module_path: Option<&'static str>,
+    file: Option<&'static str>,
+    line: Option<u32>,
+}
+
+impl<S, P> ResponseFuture<S, P>
+where
+    S: Service,
+{
+    fn log_retry<E: fmt::Display>(&self, attempt: usize, delay: Duration, error: &E) {
+        log::Log::log(
+            log::logger(),
+            &log::RecordBuilder::new()
+                .level(self.level)
+                .file(self.file.or_else(|| Some(file!())))
+                .line(self.line.or_else(|| Some(line!())))
+                .target(
+                    self.target
+                        .or(self.module_path)
+                        .unwrap_or_else(|| module_path!())
+                )
+                .module_path(
+                    self.module_path
+                        .or(self.target)
+                        .or_else(|| Some(module_path!())))
+                .args(format_args!(
+                    "retrying after error (attempt {}, backoff {:?}): {}",
+                    attempt, delay, error
+                ))
+                .build()
+        )
+    }
+}
+
+impl<S, P> Future for ResponseFuture<S, P>
+where
+    S: Service + Clone,
+    S::Request: Clone,
+    S::Error: fmt::Display,
+    P: Fn(&S::Error) -> bool,
+{
+    type Item = S::Response;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            let next = match self.state {
+                State::Called(ref mut future) => {
+                    match future.poll() {
+                        Ok(Async::Ready(response)) => return Ok(Async::Ready(response)),
+                        Ok(Async::NotReady) => return Ok(Async::NotReady),
+                        Err(error) => {
+                            let attempt = self.attempt + 1;
+                            if !(self.should_retry)(&error) {
+                                return Err(error);
+                            }
+                            match self.backoff.delay_for(attempt) {
+                                Some(delay) => {
+                                    self.log_retry(attempt, delay, &error);
+                                    self.attempt = attempt;
+                                    State::Waiting(Delay::new(Instant::now() + delay))
+                                }
+                                None => return Err(error),
+                            }
+                        }
+                    }
+                }
+                State::Waiting(ref mut delay) => {
+                    match delay.poll() {
+                        Ok(Async::Ready(())) | Err(_) => State::PollReady,
+                        Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    }
+                }
+                State::PollReady => {
+                    // `service` was cloned out of `RetryLogged` and has never
+                    // been polled on its own account, so its readiness can't
+                    // be assumed from the original's — `call` requires a
+                    // preceding `Ready` on this exact instance.
+                    match self.service.poll_ready() {
+                        Ok(Async::Ready(())) => {
+                            State::Called(self.service.call(self.request.clone()))
+                        }
+                        Ok(Async::NotReady) => return Ok(Async::NotReady),
+                        Err(error) => return Err(error),
+                    }
+                }
+            };
+            self.state = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_for_rejects_out_of_range_attempts() {
+        let backoff = ExponentialBackoff::new(
+            Duration::from_millis(100),
+            Duration::from_secs(10),
+            2.0,
+            3,
+        );
+        assert_eq!(backoff.delay_for(0), None);
+        assert_eq!(backoff.delay_for(4), None);
+    }
+
+    #[test]
+    fn delay_for_grows_exponentially_without_jitter() {
+        let backoff = ExponentialBackoff::new(
+            Duration::from_millis(100),
+            Duration::from_secs(10),
+            2.0,
+            3,
+        );
+        assert_eq!(backoff.delay_for(1), Some(Duration::from_millis(100)));
+        assert_eq!(backoff.delay_for(2), Some(Duration::from_millis(200)));
+        assert_eq!(backoff.delay_for(3), Some(Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn delay_for_caps_at_max_delay() {
+        let backoff = ExponentialBackoff::new(
+            Duration::from_millis(100),
+            Duration::from_millis(250),
+            2.0,
+            4,
+        );
+        assert_eq!(backoff.delay_for(3), Some(Duration::from_millis(250)));
+        assert_eq!(backoff.delay_for(4), Some(Duration::from_millis(250)));
+    }
+}
@@ -0,0 +1,194 @@
+//! Sampling and rate limiting for [`LogErrors`] and [`LogResponses`].
+//!
+//! By default every record produced by the middleware in this crate is
+//! logged. When a wrapped service fails (or responds) on every poll, that
+//! can overwhelm a log pipeline; [`Sampler`] lets records be thinned out
+//! either probabilistically or with a token-bucket rate limit.
+//!
+//! [`LogErrors`]: ../struct.LogErrors.html
+//! [`LogResponses`]: ../struct.LogResponses.html
+
+extern crate rand;
+
+use std::time::{Duration, Instant};
+
+/// Controls how many of the records produced by [`LogErrors`] or
+/// [`LogResponses`] are actually emitted.
+///
+/// [`LogErrors`]: ../struct.LogErrors.html
+/// [`LogResponses`]: ../struct.LogResponses.html
+#[derive(Clone, Debug)]
+pub struct Sampler {
+    mode: Mode,
+}
+
+#[derive(Clone, Debug)]
+enum Mode {
+    /// Log every record.
+    Always,
+    /// Log roughly 1-in-`1 / rate` records.
+    Probabilistic { rate: f64 },
+    /// Allow up to `max` records per `interval`, dropping the rest.
+    RateLimited {
+        max: u32,
+        interval: Duration,
+        window_start: Instant,
+        count: u32,
+        suppressed: u32,
+    },
+}
+
+/// The result of sampling a single record.
+#[derive(Debug)]
+pub enum SampleOutcome {
+    /// The record should be logged as usual.
+    Log,
+    /// The record should be logged, and a prior window suppressed this many
+    /// records that should be summarized first.
+    LogWithSuppressed(u32),
+    /// The record should be dropped.
+    Drop,
+}
+
+impl Sampler {
+
+    /// Log every record. This is the default.
+    pub fn always() -> Self {
+        Sampler { mode: Mode::Always }
+    }
+
+    /// Log roughly 1-in-`1 / rate` records, chosen independently at random.
+    ///
+    /// `rate` should be in `[0.0, 1.0]`; a `rate` of `1.0` logs every record
+    /// and a `rate` of `0.0` logs none.
+    pub fn sample_rate(rate: f64) -> Self {
+        Sampler { mode: Mode::Probabilistic { rate } }
+    }
+
+    /// Allow up to `max` records per `interval`, dropping the rest.
+    ///
+    /// When the window rolls over, if any records were suppressed during the
+    /// prior window, the next allowed record is preceded by a summary noting
+    /// how many were dropped.
+    ///
+    /// `max` is clamped to at least `1`: a `max` of `0` would drop every
+    /// record forever with no window ever allowing the suppressed-count
+    /// summary through.
+    pub fn max_per_interval(max: u32, interval: Duration) -> Self {
+        Sampler {
+            mode: Mode::RateLimited {
+                max: max.max(1),
+                interval,
+                window_start: Instant::now(),
+                count: 0,
+                suppressed: 0,
+            },
+        }
+    }
+
+    /// Decide whether the next record should be logged, updating any
+    /// internal counter/bucket state as a side effect.
+    pub fn sample(&mut self) -> SampleOutcome {
+        match self.mode {
+            Mode::Always => SampleOutcome::Log,
+            Mode::Probabilistic { rate } => {
+                if rand::random::<f64>() < rate {
+                    SampleOutcome::Log
+                } else {
+                    SampleOutcome::Drop
+                }
+            }
+            Mode::RateLimited { max, interval, ref mut window_start, ref mut count, ref mut suppressed } => {
+                let mut rolled_over_suppressed = None;
+                if window_start.elapsed() >= interval {
+                    if *suppressed > 0 {
+                        rolled_over_suppressed = Some(*suppressed);
+                    }
+                    *window_start = Instant::now();
+                    *count = 0;
+                    *suppressed = 0;
+                }
+
+                if *count < max {
+                    *count += 1;
+                    match rolled_over_suppressed {
+                        Some(suppressed) => SampleOutcome::LogWithSuppressed(suppressed),
+                        None => SampleOutcome::Log,
+                    }
+                } else {
+                    *suppressed += 1;
+                    SampleOutcome::Drop
+                }
+            }
+        }
+    }
+
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler::always()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn always_never_drops() {
+        let mut sampler = Sampler::always();
+        for _ in 0..10 {
+            assert!(matches!(sampler.sample(), SampleOutcome::Log));
+        }
+    }
+
+    #[test]
+    fn rate_limited_allows_up_to_max_per_window() {
+        let mut sampler = Sampler::max_per_interval(2, Duration::from_secs(60));
+        assert!(matches!(sampler.sample(), SampleOutcome::Log));
+        assert!(matches!(sampler.sample(), SampleOutcome::Log));
+        assert!(matches!(sampler.sample(), SampleOutcome::Drop));
+        assert!(matches!(sampler.sample(), SampleOutcome::Drop));
+    }
+
+    #[test]
+    fn rate_limited_reports_suppressed_count_on_rollover() {
+        let mut sampler = Sampler::max_per_interval(1, Duration::from_millis(10));
+        assert!(matches!(sampler.sample(), SampleOutcome::Log));
+        assert!(matches!(sampler.sample(), SampleOutcome::Drop));
+        assert!(matches!(sampler.sample(), SampleOutcome::Drop));
+
+        thread::sleep(Duration::from_millis(20));
+
+        match sampler.sample() {
+            SampleOutcome::LogWithSuppressed(suppressed) => assert_eq!(suppressed, 2),
+            other => panic!("expected LogWithSuppressed(2), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn max_per_interval_clamps_zero_max_to_one() {
+        let mut sampler = Sampler::max_per_interval(0, Duration::from_millis(10));
+        assert!(matches!(sampler.sample(), SampleOutcome::Log));
+        assert!(matches!(sampler.sample(), SampleOutcome::Drop));
+
+        thread::sleep(Duration::from_millis(20));
+
+        match sampler.sample() {
+            SampleOutcome::LogWithSuppressed(suppressed) => assert_eq!(suppressed, 1),
+            other => panic!("expected LogWithSuppressed(1), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rate_limited_window_without_suppression_logs_plainly() {
+        let mut sampler = Sampler::max_per_interval(1, Duration::from_millis(10));
+        assert!(matches!(sampler.sample(), SampleOutcome::Log));
+
+        thread::sleep(Duration::from_millis(20));
+
+        assert!(matches!(sampler.sample(), SampleOutcome::Log));
+    }
+}